@@ -0,0 +1,240 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{anyhow, Context, Result};
+use ethers::core::types::Address;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+
+/// The set of addresses permitted to reach protected handlers, each mapped to
+/// an optional role string. When no source is configured the allowlist is
+/// disabled and every recovered signer is accepted. A configured file is
+/// watched for changes so operators can revoke access without a restart.
+pub(crate) struct Allowlist {
+    enabled: bool,
+    entries: RwLock<HashMap<Address, Option<String>>>,
+    /// Non-Ethereum principals (HTTP-signature `keyId`s) permitted to reach
+    /// protected handlers, from `HTTP_SIG_KEY_ALLOWLIST`. Kept separate from
+    /// `entries` because a `keyId` is an opaque string, not an [`Address`].
+    key_ids: RwLock<HashMap<String, Option<String>>>,
+}
+
+impl Allowlist {
+    /// Build the allowlist from `ALLOWLIST_PATH` (a JSON/TOML file), the
+    /// `ALLOWLIST` env var (a comma-separated list of addresses) and/or
+    /// `HTTP_SIG_KEY_ALLOWLIST` (a comma-separated list of HTTP-signature
+    /// `keyId`s). Absent all three, the allowlist is disabled and every
+    /// recovered signer or keyId is accepted.
+    pub(crate) fn from_env() -> Self {
+        let key_ids = RwLock::new(key_ids_from_env());
+
+        if let Some(path) = allowlist_path() {
+            let entries = match Self::read_file(&path) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    log::warn!("Failed to load allowlist {}: {e:?}", path.display());
+                    HashMap::new()
+                }
+            };
+            return Self {
+                enabled: true,
+                entries: RwLock::new(entries),
+                key_ids,
+            };
+        }
+
+        if let Ok(list) = env::var("ALLOWLIST") {
+            let entries = list
+                .split(',')
+                .filter_map(|a| Address::from_str(a.trim()).ok())
+                .map(|a| (a, None))
+                .collect();
+            return Self {
+                enabled: true,
+                entries: RwLock::new(entries),
+                key_ids,
+            };
+        }
+
+        if !key_ids.read().expect("allowlist poisoned").is_empty() {
+            return Self {
+                enabled: true,
+                entries: RwLock::new(HashMap::new()),
+                key_ids,
+            };
+        }
+
+        Self {
+            enabled: false,
+            entries: RwLock::new(HashMap::new()),
+            key_ids,
+        }
+    }
+
+    /// Parse an allowlist file as either a JSON/TOML object mapping address to
+    /// role, or a JSON array of bare addresses. A TOML document is always a
+    /// table, so `.toml` files accept only the object form.
+    fn read_file(path: &Path) -> Result<HashMap<Address, Option<String>>> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading allowlist {}", path.display()))?;
+
+        let raw: AllowlistFile = if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            toml::from_str(&text).context("parsing allowlist TOML")?
+        } else {
+            serde_json::from_str(&text).context("parsing allowlist JSON")?
+        };
+
+        let map = match raw {
+            AllowlistFile::Roles(roles) => roles
+                .into_iter()
+                .filter_map(|(addr, role)| Address::from_str(&addr).ok().map(|a| (a, Some(role))))
+                .collect(),
+            AllowlistFile::Addresses(list) => list
+                .into_iter()
+                .filter_map(|addr| Address::from_str(&addr).ok().map(|a| (a, None)))
+                .collect(),
+        };
+        Ok(map)
+    }
+
+    /// Authorize a recovered signer, returning its role on success. Returns an
+    /// error (surfaced as HTTP 403) when the allowlist is enabled and the
+    /// address is unknown.
+    pub(crate) fn authorize(&self, address: &Address) -> Result<Option<String>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let entries = self.entries.read().expect("allowlist poisoned");
+        match entries.get(address) {
+            Some(role) => Ok(role.clone()),
+            None => Err(anyhow!("address {address:?} is not on the allowlist")),
+        }
+    }
+
+    /// Authorize an HTTP-signature `keyId`, returning its role on success.
+    /// Returns an error (surfaced as HTTP 403) when the allowlist is enabled
+    /// and the `keyId` is not in `HTTP_SIG_KEY_ALLOWLIST`.
+    pub(crate) fn authorize_key_id(&self, key_id: &str) -> Result<Option<String>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let key_ids = self.key_ids.read().expect("allowlist poisoned");
+        match key_ids.get(key_id) {
+            Some(role) => Ok(role.clone()),
+            None => Err(anyhow!("keyId {key_id:?} is not on the allowlist")),
+        }
+    }
+
+    /// Reload the entries from `path`, swapping them in atomically.
+    fn reload(&self, path: &Path) {
+        match Self::read_file(path) {
+            Ok(entries) => {
+                *self.entries.write().expect("allowlist poisoned") = entries;
+                log::info!("Reloaded allowlist from {}", path.display());
+            }
+            Err(e) => log::warn!("Failed to reload allowlist: {e:?}"),
+        }
+    }
+}
+
+/// The two accepted on-disk shapes for an allowlist file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AllowlistFile {
+    Roles(HashMap<String, String>),
+    Addresses(Vec<String>),
+}
+
+fn allowlist_path() -> Option<PathBuf> {
+    env::var("ALLOWLIST_PATH").ok().map(PathBuf::from)
+}
+
+/// Parse `HTTP_SIG_KEY_ALLOWLIST` into a set of permitted `keyId`s, each
+/// unscoped (no role). Absent the env var, returns an empty map.
+fn key_ids_from_env() -> HashMap<String, Option<String>> {
+    env::var("HTTP_SIG_KEY_ALLOWLIST")
+        .ok()
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(|k| (k.to_string(), None))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Start watching the allowlist file, reloading it on every change. The
+/// returned watcher must be kept alive for the duration of the process.
+pub(crate) fn watch_allowlist(allowlist: Arc<Allowlist>) -> Option<RecommendedWatcher> {
+    let path = allowlist_path()?;
+    let reload_path = path.clone();
+    let mut watcher = match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if res.is_ok() {
+            allowlist.reload(&reload_path);
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            log::warn!("Failed to create allowlist watcher: {e:?}");
+            return None;
+        }
+    };
+
+    if let Err(e) = watcher.watch(&path, RecursiveMode::NonRecursive) {
+        log::warn!("Failed to watch allowlist {}: {e:?}", path.display());
+        return None;
+    }
+    Some(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::now;
+
+    #[test]
+    fn allowlist_read_file_parses_roles_object() {
+        let path = std::env::temp_dir().join(format!(
+            "allowlist-roles-{}-{}.json",
+            std::process::id(),
+            now()
+        ));
+        std::fs::write(
+            &path,
+            r#"{"0x0000000000000000000000000000000000000001": "admin"}"#,
+        )
+        .unwrap();
+
+        let entries = Allowlist::read_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let addr = Address::from_low_u64_be(1);
+        assert_eq!(entries.get(&addr), Some(&Some("admin".to_string())));
+    }
+
+    #[test]
+    fn allowlist_read_file_parses_bare_address_array() {
+        let path = std::env::temp_dir().join(format!(
+            "allowlist-addrs-{}-{}.json",
+            std::process::id(),
+            now()
+        ));
+        std::fs::write(
+            &path,
+            r#"["0x0000000000000000000000000000000000000002"]"#,
+        )
+        .unwrap();
+
+        let entries = Allowlist::read_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let addr = Address::from_low_u64_be(2);
+        assert_eq!(entries.get(&addr), Some(&None));
+    }
+}
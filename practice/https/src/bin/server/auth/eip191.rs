@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use ethers::{
+    core::types::{Address, Signature, H256},
+    utils::keccak256,
+};
+
+/// The EIP-191 signing scheme selected by the leading version byte. `0x45`
+/// ("E") is `personal_sign`, `0x00` is the presigned/validator form and `0x01`
+/// defers to structured (EIP-712) data.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Eip191Version {
+    /// `0x00` — `keccak256(0x19 ‖ 0x00 ‖ validator ‖ data)`.
+    Validator,
+    /// `0x45` — the `personal_sign` scheme wallets use by default.
+    PersonalSign,
+    /// `0x01` — structured data, handled by the EIP-712 path.
+    TypedData,
+}
+
+impl Eip191Version {
+    /// Parse the version from its hex byte (`0x00` / `0x01` / `0x45`) or its
+    /// common alias (`validator` / `eip712` / `personal_sign`).
+    pub(crate) fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "0x00" | "00" | "validator" => Ok(Self::Validator),
+            "0x01" | "01" | "eip712" => Ok(Self::TypedData),
+            "0x45" | "45" | "personal_sign" => Ok(Self::PersonalSign),
+            other => Err(anyhow!("unsupported EIP-191 version: {other}")),
+        }
+    }
+}
+
+/// Build the EIP-191 digest for `message` under the selected `version`. The raw
+/// message bytes are concatenated directly — never debug-formatted — so the
+/// digest matches MetaMask/ethers output.
+pub(crate) fn eip191_digest(
+    version: Eip191Version,
+    message: &[u8],
+    validator: Option<Address>,
+) -> Result<[u8; 32]> {
+    let data = match version {
+        Eip191Version::PersonalSign => {
+            let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+            data.extend_from_slice(message);
+            data
+        }
+        Eip191Version::Validator => {
+            let validator = validator
+                .ok_or_else(|| anyhow!("validator version requires a configured address"))?;
+            let mut data = Vec::with_capacity(2 + Address::len_bytes() + message.len());
+            data.push(0x19);
+            data.push(0x00);
+            data.extend_from_slice(validator.as_bytes());
+            data.extend_from_slice(message);
+            data
+        }
+        Eip191Version::TypedData => {
+            return Err(anyhow!("EIP-712 uses the typed-data path, not eip191_digest"))
+        }
+    };
+    Ok(keccak256(data))
+}
+
+/// Recover the signer of `message` under the given EIP-191 `version`.
+pub(crate) fn recover_address_from_signature(
+    message: &[u8],
+    signature_str: &str,
+    version: Eip191Version,
+    validator: Option<Address>,
+) -> Result<Address> {
+    let digest = eip191_digest(version, message, validator)?;
+
+    // Convert the signature to its r, s, and v components
+    let signature = Signature::from_str(signature_str).context("Failed to parse signature")?;
+
+    // Recover the Ethereum address
+    let address = signature
+        .recover(H256::from(digest))
+        .context("Failed to recover address")?;
+
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eip191_digest_personal_sign_matches_hand_built_prefix() {
+        let message = b"hello world";
+        let mut expected = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+        expected.extend_from_slice(message);
+        let expected = keccak256(expected);
+
+        let digest = eip191_digest(Eip191Version::PersonalSign, message, None).unwrap();
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn eip191_digest_validator_binds_validator_address() {
+        let message = b"approve";
+        let validator = Address::from_low_u64_be(0x1234);
+
+        let digest_a = eip191_digest(Eip191Version::Validator, message, Some(validator)).unwrap();
+        let digest_b = eip191_digest(
+            Eip191Version::Validator,
+            message,
+            Some(Address::from_low_u64_be(0x5678)),
+        )
+        .unwrap();
+        assert_ne!(digest_a, digest_b, "digest must depend on the validator address");
+
+        let err = eip191_digest(Eip191Version::Validator, message, None).unwrap_err();
+        assert!(err.to_string().contains("validator"));
+    }
+
+    #[test]
+    fn eip191_digest_rejects_typed_data_version() {
+        let err = eip191_digest(Eip191Version::TypedData, b"x", None).unwrap_err();
+        assert!(err.to_string().contains("EIP-712"));
+    }
+
+    #[test]
+    fn eip191_version_parses_bytes_and_aliases() {
+        assert!(Eip191Version::parse("0x45").unwrap() == Eip191Version::PersonalSign);
+        assert!(Eip191Version::parse("personal_sign").unwrap() == Eip191Version::PersonalSign);
+        assert!(Eip191Version::parse("0x00").unwrap() == Eip191Version::Validator);
+        assert!(Eip191Version::parse("eip712").unwrap() == Eip191Version::TypedData);
+        assert!(Eip191Version::parse("bogus").is_err());
+    }
+}
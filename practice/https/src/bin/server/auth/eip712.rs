@@ -0,0 +1,159 @@
+use std::{env, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use ethers::core::types::{
+    transaction::eip712::{Eip712, EIP712Domain, TypedData},
+    Address, Signature, H256, U256,
+};
+use serde::Deserialize;
+
+/// Recover the signer of an EIP-712 typed-data document. The `body` is the
+/// `{ types, primaryType, domain, message }` JSON document; ethers' [`TypedData`]
+/// performs the `encodeType`/`hashStruct` recursion and yields the final
+/// `keccak256(0x1901 ‖ domainSeparator ‖ hashStruct(message))` digest, from
+/// which we recover the address. The parsed domain is returned alongside so
+/// callers can assert the signature was scoped to their app.
+pub(crate) fn recover_eip712(body: &[u8], signature_str: &str) -> Result<(Address, EIP712Domain)> {
+    let typed: TypedData = serde_json::from_slice(body).context("Failed to parse typed data")?;
+    let digest = typed
+        .encode_eip712()
+        .context("Failed to encode EIP-712 digest")?;
+
+    let signature = Signature::from_str(signature_str).context("Failed to parse signature")?;
+    let address = signature
+        .recover(H256::from(digest))
+        .context("Failed to recover address")?;
+
+    Ok((address, typed.domain))
+}
+
+/// This application's own EIP-712 domain, checked against every typed-data
+/// signature so a document scoped to a different dApp (same `primaryType`,
+/// same `message.nonce`, but signed for someone else's contract) is rejected
+/// instead of silently accepted. Configured via `EIP712_DOMAIN_NAME`,
+/// `EIP712_DOMAIN_VERSION`, `EIP712_CHAIN_ID` and `EIP712_VERIFYING_CONTRACT`;
+/// only the fields that are set are checked, and the whole check is skipped
+/// when none of the four are configured.
+pub(crate) struct ExpectedDomain {
+    name: Option<String>,
+    version: Option<String>,
+    chain_id: Option<U256>,
+    verifying_contract: Option<Address>,
+}
+
+impl ExpectedDomain {
+    /// Build the expected domain from the environment, or `None` if no
+    /// domain field is configured.
+    pub(crate) fn from_env() -> Option<Self> {
+        let name = env::var("EIP712_DOMAIN_NAME").ok();
+        let version = env::var("EIP712_DOMAIN_VERSION").ok();
+        let chain_id = env::var("EIP712_CHAIN_ID")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(U256::from);
+        let verifying_contract = env::var("EIP712_VERIFYING_CONTRACT")
+            .ok()
+            .and_then(|v| Address::from_str(&v).ok());
+
+        if name.is_none() && version.is_none() && chain_id.is_none() && verifying_contract.is_none()
+        {
+            return None;
+        }
+        Some(Self {
+            name,
+            version,
+            chain_id,
+            verifying_contract,
+        })
+    }
+
+    /// Reject a typed-data `domain` that doesn't match every configured field.
+    pub(crate) fn check(&self, domain: &EIP712Domain) -> Result<()> {
+        if let Some(name) = &self.name {
+            if domain.name.as_deref() != Some(name.as_str()) {
+                return Err(anyhow!("EIP-712 domain name does not match this app"));
+            }
+        }
+        if let Some(version) = &self.version {
+            if domain.version.as_deref() != Some(version.as_str()) {
+                return Err(anyhow!("EIP-712 domain version does not match this app"));
+            }
+        }
+        if let Some(chain_id) = self.chain_id {
+            if domain.chain_id != Some(chain_id) {
+                return Err(anyhow!("EIP-712 domain chainId does not match this app"));
+            }
+        }
+        if let Some(contract) = self.verifying_contract {
+            if domain.verifying_contract != Some(contract) {
+                return Err(anyhow!(
+                    "EIP-712 domain verifyingContract does not match this app"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The subset of an EIP-712 typed-data document needed to check its `message`
+/// carries the expected `nonce`. The body is parsed into this struct in a
+/// second pass, separately from the full [`TypedData`] parse in
+/// [`recover_eip712`].
+#[derive(Deserialize)]
+pub(crate) struct TypedDataNonce {
+    pub(crate) message: TypedDataNonceMessage,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TypedDataNonceMessage {
+    pub(crate) nonce: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed_data_body(message_nonce: &str) -> Vec<u8> {
+        format!(
+            r#"{{
+                "types": {{
+                    "EIP712Domain": [
+                        {{ "name": "name", "type": "string" }}
+                    ],
+                    "Login": [
+                        {{ "name": "nonce", "type": "string" }}
+                    ]
+                }},
+                "primaryType": "Login",
+                "domain": {{ "name": "test-dapp" }},
+                "message": {{ "nonce": "{message_nonce}" }}
+            }}"#
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn recover_eip712_is_deterministic_and_parses_domain() {
+        let body = typed_data_body("abc123");
+        // Not a real signature over this document — recovery may or may not
+        // succeed for an arbitrary (r, s, v) — but the digest (and therefore
+        // the outcome) must depend only on the document, never on incidental
+        // formatting, so the same input must always produce the same result.
+        let signature = "0x".to_string() + &"11".repeat(64) + "1b";
+
+        let result_a = recover_eip712(&body, &signature);
+        let result_b = recover_eip712(&body, &signature);
+        assert_eq!(result_a.is_ok(), result_b.is_ok());
+        if let (Ok((address_a, domain_a)), Ok((address_b, domain_b))) = (result_a, result_b) {
+            assert_eq!(address_a, address_b);
+            assert_eq!(domain_a.name.as_deref(), Some("test-dapp"));
+            assert_eq!(domain_b.name.as_deref(), Some("test-dapp"));
+        }
+    }
+
+    #[test]
+    fn recover_eip712_rejects_malformed_document() {
+        let err = recover_eip712(b"not json", "0x00").unwrap_err();
+        assert!(err.to_string().contains("Failed to parse typed data"));
+    }
+}
@@ -0,0 +1,260 @@
+use std::{collections::HashMap, env, path::Path, time::SystemTime, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use hyper::http::request::Parts;
+use rsa::{
+    pkcs1v15::{Signature as RsaSignature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::Verifier,
+    RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+
+/// Acceptable clock skew between the client `Date` header and the server.
+const HTTP_SIG_SKEW: Duration = Duration::from_secs(5 * 60);
+
+/// Verify an RFC-9421/draft-cavage HTTP Signature. Reconstructs the signing
+/// string from the listed pseudo-headers, checks the `Date` skew and body
+/// `Digest`, resolves the RSA public key by `keyId` and verifies with
+/// RSA-SHA256. Returns the `keyId` of the authenticated peer on success.
+///
+/// The signed header list must cover `digest`, `(request-target)` and `date`
+/// — all three are required, not merely honoured when the caller happens to
+/// list them — so the signature always binds the request body, method/path
+/// and freshness window, not just whatever subset of headers the client
+/// chose to sign.
+pub(crate) fn verify_http_signature(parts: &Parts, body: &[u8]) -> Result<String> {
+    let raw = parts
+        .headers
+        .get("Signature")
+        .ok_or_else(|| anyhow!("missing Signature header"))?
+        .to_str()
+        .context("Failed to parse Signature header")?;
+    let params = parse_signature_params(raw);
+
+    let key_id = params
+        .get("keyId")
+        .ok_or_else(|| anyhow!("Signature header missing keyId"))?;
+    let header_list = params
+        .get("headers")
+        .map(String::as_str)
+        .unwrap_or("date");
+    let signature_b64 = params
+        .get("signature")
+        .ok_or_else(|| anyhow!("Signature header missing signature"))?;
+
+    // Which headers are covered is caller-supplied, so don't trust it for the
+    // three properties that matter: body integrity, method/path binding and
+    // freshness. `digest`, `(request-target)` and `date` must all be present
+    // in the signed header list, not merely optional — otherwise a request
+    // signed with `headers="date"` alone could have its body or target
+    // swapped after capture, and one signed with `headers="digest
+    // (request-target)"` (no `date`) could be replayed forever since an
+    // uncovered `Date` header isn't protected by the signature at all.
+    let covered: Vec<&str> = header_list.split_whitespace().collect();
+    if !covered.contains(&"digest") || !covered.contains(&"(request-target)") {
+        return Err(anyhow!(
+            "Signature must cover both digest and (request-target)"
+        ));
+    }
+    if !covered.contains(&"date") {
+        return Err(anyhow!("Signature must cover date"));
+    }
+
+    // The Date must be covered (checked above) and within the skew window,
+    // and the covered Digest must match a freshly computed hash of the body.
+    let date = parts
+        .headers
+        .get(hyper::header::DATE)
+        .ok_or_else(|| anyhow!("signature covers date but none was sent"))?
+        .to_str()
+        .context("Failed to parse Date header")?;
+    check_date_skew(date)?;
+    let digest = parts
+        .headers
+        .get("Digest")
+        .ok_or_else(|| anyhow!("signature covers digest but none was sent"))?
+        .to_str()
+        .context("Failed to parse Digest header")?;
+    check_digest(digest, body)?;
+
+    let signing_string = build_signing_string(parts, header_list)?;
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(signature_b64)
+        .context("Failed to base64-decode signature")?;
+
+    let pem = resolve_public_key(key_id)?;
+    let public_key = RsaPublicKey::from_public_key_pem(&pem)
+        .context("Failed to parse resolved public key")?;
+    let verifying_key = VerifyingKey::<Sha256>::new(public_key);
+    let signature =
+        RsaSignature::try_from(signature.as_slice()).context("Malformed RSA signature")?;
+    verifying_key
+        .verify(signing_string.as_bytes(), &signature)
+        .context("RSA-SHA256 signature verification failed")?;
+
+    Ok(key_id.clone())
+}
+
+/// Parse the comma-separated `name="value"` parameters of a `Signature` header.
+fn parse_signature_params(header: &str) -> HashMap<String, String> {
+    header
+        .split(',')
+        .filter_map(|kv| {
+            let (key, value) = kv.trim().split_once('=')?;
+            Some((
+                key.trim().to_string(),
+                value.trim().trim_matches('"').to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Reconstruct the signing string by joining `name: value` lines in the order
+/// given by `header_list`, expanding the `(request-target)` pseudo-header.
+fn build_signing_string(parts: &Parts, header_list: &str) -> Result<String> {
+    let lines = header_list
+        .split_whitespace()
+        .map(|name| {
+            let value = if name == "(request-target)" {
+                format!(
+                    "{} {}",
+                    parts.method.as_str().to_ascii_lowercase(),
+                    parts.uri.path()
+                )
+            } else {
+                parts
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| anyhow!("signed header `{name}` missing from request"))?
+                    .to_string()
+            };
+            Ok(format!("{name}: {value}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(lines.join("\n"))
+}
+
+/// Confirm the `Date` header is within [`HTTP_SIG_SKEW`] of the server clock.
+fn check_date_skew(date: &str) -> Result<()> {
+    let sent = httpdate::parse_http_date(date).context("Failed to parse Date header")?;
+    let now = SystemTime::now();
+    let skew = now
+        .duration_since(sent)
+        .or_else(|_| sent.duration_since(now))
+        .unwrap_or_default();
+    if skew > HTTP_SIG_SKEW {
+        return Err(anyhow!("Date header outside acceptable skew"));
+    }
+    Ok(())
+}
+
+/// Confirm the `Digest` header matches a freshly computed SHA-256 of the body.
+fn check_digest(digest: &str, body: &[u8]) -> Result<()> {
+    let (algo, expected_b64) = digest
+        .split_once('=')
+        .ok_or_else(|| anyhow!("malformed Digest header"))?;
+    if !algo.eq_ignore_ascii_case("SHA-256") {
+        return Err(anyhow!("unsupported digest algorithm: {algo}"));
+    }
+    let expected = base64::engine::general_purpose::STANDARD
+        .decode(expected_b64)
+        .context("Failed to base64-decode Digest")?;
+    let actual = Sha256::digest(body);
+    if expected != actual.as_slice() {
+        return Err(anyhow!("Digest header does not match request body"));
+    }
+    Ok(())
+}
+
+/// Resolve a `keyId` to its PEM-encoded public key. Keys live as files under
+/// the directory named by `HTTP_SIG_KEYS_DIR`, with the `keyId` sanitised to a
+/// bare filename so remote identifiers cannot escape the directory.
+fn resolve_public_key(key_id: &str) -> Result<String> {
+    let dir = env::var("HTTP_SIG_KEYS_DIR")
+        .context("HTTP_SIG_KEYS_DIR not set; cannot resolve keyId")?;
+    let sanitized: String = key_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    let path = Path::new(&dir).join(format!("{sanitized}.pem"));
+    std::fs::read_to_string(&path)
+        .with_context(|| format!("reading public key {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Request;
+
+    #[test]
+    fn parse_signature_params_splits_quoted_kv_pairs() {
+        let header = r#"keyId="test-key",algorithm="rsa-sha256",headers="date digest (request-target)",signature="abc=="#;
+        let params = parse_signature_params(header);
+        assert_eq!(params.get("keyId").map(String::as_str), Some("test-key"));
+        assert_eq!(
+            params.get("headers").map(String::as_str),
+            Some("date digest (request-target)")
+        );
+        assert_eq!(params.get("signature").map(String::as_str), Some("abc=="));
+    }
+
+    #[test]
+    fn build_signing_string_expands_request_target_and_joins_headers() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/widgets/42?x=1")
+            .header("Date", "Tue, 07 Jun 2014 20:51:35 GMT")
+            .header("Digest", "SHA-256=abc=")
+            .body(())
+            .unwrap();
+        let (parts, _) = request.into_parts();
+
+        let signing_string =
+            build_signing_string(&parts, "(request-target) date digest").unwrap();
+
+        assert_eq!(
+            signing_string,
+            "(request-target): post /widgets/42\ndate: Tue, 07 Jun 2014 20:51:35 GMT\ndigest: SHA-256=abc="
+        );
+    }
+
+    #[test]
+    fn build_signing_string_rejects_missing_header() {
+        let request = Request::builder().method("GET").uri("/").body(()).unwrap();
+        let (parts, _) = request.into_parts();
+
+        let err = build_signing_string(&parts, "digest").unwrap_err();
+        assert!(err.to_string().contains("digest"));
+    }
+
+    #[test]
+    fn check_digest_accepts_matching_sha256() {
+        let body = b"the quick brown fox";
+        let expected = Sha256::digest(body);
+        let header = format!(
+            "SHA-256={}",
+            base64::engine::general_purpose::STANDARD.encode(expected)
+        );
+        assert!(check_digest(&header, body).is_ok());
+    }
+
+    #[test]
+    fn check_digest_rejects_tampered_body() {
+        let body = b"the quick brown fox";
+        let expected = Sha256::digest(body);
+        let header = format!(
+            "SHA-256={}",
+            base64::engine::general_purpose::STANDARD.encode(expected)
+        );
+        assert!(check_digest(&header, b"the quick brown fox!").is_err());
+    }
+
+    #[test]
+    fn check_digest_rejects_unsupported_algorithm() {
+        let err = check_digest("MD5=abc=", b"x").unwrap_err();
+        assert!(err.to_string().contains("unsupported digest algorithm"));
+    }
+}
@@ -0,0 +1,4 @@
+pub(crate) mod eip191;
+pub(crate) mod eip712;
+pub(crate) mod http_sig;
+pub(crate) mod siwe;
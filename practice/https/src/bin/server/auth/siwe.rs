@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use ethers::core::types::Address;
+use hyper::{header, Body, Request, Response, StatusCode};
+use serde::Deserialize;
+
+use crate::auth::eip191::{recover_address_from_signature, Eip191Version};
+use crate::{text_response, AppState, SESSION_TTL};
+
+/// The SIWE-style payload the client posts to `/verify`. The signed message is
+/// reconstructed verbatim server-side so the recovered address is bound to this
+/// exact nonce, domain and timestamp.
+#[derive(Deserialize)]
+pub(crate) struct VerifyRequest {
+    domain: String,
+    address: Address,
+    nonce: String,
+    issued_at: u64,
+    signature: String,
+}
+
+/// Canonical text that both client and server hash. Keeping a single builder
+/// guarantees the two sides agree byte-for-byte.
+pub(crate) fn siwe_message(domain: &str, address: Address, nonce: &str, issued_at: u64) -> String {
+    format!(
+        "{domain} wants you to sign in with your Ethereum account:\n{:?}\n\nNonce: {nonce}\nIssued At: {issued_at}",
+        address
+    )
+}
+
+pub(crate) fn handle_nonce(state: &AppState) -> Result<Response<Body>> {
+    let nonce = state.issue_nonce();
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(nonce.into())
+        .context("Failed to build nonce response")
+}
+
+pub(crate) async fn handle_verify(state: &AppState, req: Request<Body>) -> Result<Response<Body>> {
+    let body_bytes = hyper::body::to_bytes(req.into_body()).await?;
+    // hyper turns a service Err into a dropped connection, not an HTTP
+    // response, so every fallible step below is caught and converted to a
+    // proper status instead of propagating via `?` — the same pattern
+    // handle_request already uses around `authenticate()`.
+    let verify: VerifyRequest = match serde_json::from_slice(&body_bytes) {
+        Ok(verify) => verify,
+        Err(e) => {
+            return text_response(
+                StatusCode::BAD_REQUEST,
+                &format!("Failed to parse verify body: {e:?}"),
+            )
+        }
+    };
+
+    let message = siwe_message(&verify.domain, verify.address, &verify.nonce, verify.issued_at);
+    let recovered = match recover_address_from_signature(
+        message.as_bytes(),
+        &verify.signature,
+        Eip191Version::PersonalSign,
+        None,
+    ) {
+        Ok(address) => address,
+        Err(e) => return text_response(StatusCode::UNAUTHORIZED, &format!("{e:?}")),
+    };
+    if recovered != verify.address {
+        return text_response(StatusCode::UNAUTHORIZED, "signature does not match address");
+    }
+
+    if let Err(e) = state.consume_nonce(&verify.nonce) {
+        return text_response(StatusCode::UNAUTHORIZED, &format!("{e:?}"));
+    }
+
+    let token = state.issue_token(recovered);
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .header(
+            header::SET_COOKIE,
+            format!(
+                "session={token}; HttpOnly; SameSite=Strict; Max-Age={}",
+                SESSION_TTL.as_secs()
+            ),
+        )
+        .body(token.into())
+        .context("Failed to build verify response")
+}
@@ -0,0 +1,430 @@
+// Copyright (c) 2023.
+// All rights reserved by Liam Ren
+// This code is licensed under the MIT license.
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+mod allowlist;
+mod auth;
+mod signer;
+
+use std::{
+    collections::HashMap,
+    env,
+    net::Ipv4Addr,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use ethers::{
+    core::{
+        rand::{rngs::OsRng, RngCore},
+        types::{transaction::eip712::EIP712Domain, Address},
+    },
+    utils::hex,
+};
+use hmac::{Hmac, Mac};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, http::request::Parts, Body, HeaderMap, Method, Request, Response, Server, StatusCode};
+use sha2::Sha256;
+
+use allowlist::{watch_allowlist, Allowlist};
+use auth::eip191::{recover_address_from_signature, Eip191Version};
+use auth::eip712::{recover_eip712, ExpectedDomain, TypedDataNonce};
+use auth::http_sig::verify_http_signature;
+use auth::siwe::{handle_nonce, handle_verify};
+use signer::{load_signer, Signer};
+
+/// How long an issued nonce stays valid before it is rejected as stale.
+const NONCE_TTL: Duration = Duration::from_secs(5 * 60);
+/// How long a session token minted by `/verify` remains accepted.
+pub(crate) const SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// An outstanding nonce handed out by `GET /nonce` and awaiting a matching
+/// signature on `POST /verify`. Once consumed it can never be replayed.
+struct NonceEntry {
+    issued_at: u64,
+    consumed: bool,
+}
+
+/// Process-wide authentication state: the pool of live nonces plus the secret
+/// used to mint and check the HMAC session cookies. Wrapped in an [`Arc`] and
+/// shared across every connection.
+pub(crate) struct AppState {
+    nonces: Mutex<HashMap<String, NonceEntry>>,
+    token_key: [u8; 32],
+    /// This server's own address, used as the validator in `0x00` signatures.
+    validator: Option<Address>,
+    /// The server's signing identity, used to counter-sign responses.
+    signer: Option<Box<dyn Signer>>,
+    /// Addresses permitted to reach protected handlers.
+    allowlist: Arc<Allowlist>,
+    /// This app's own EIP-712 domain, checked against typed-data signatures.
+    expected_domain: Option<ExpectedDomain>,
+}
+
+impl AppState {
+    async fn new() -> Self {
+        let mut token_key = [0u8; 32];
+        OsRng.fill_bytes(&mut token_key);
+        let signer = load_signer().await;
+        // The validator address defaults to the server's own signing identity,
+        // falling back to VALIDATOR_ADDRESS when no signer is configured.
+        let validator = signer.as_ref().map(|s| s.address()).or_else(|| {
+            env::var("VALIDATOR_ADDRESS")
+                .ok()
+                .and_then(|v| Address::from_str(&v).ok())
+        });
+        Self {
+            nonces: Mutex::new(HashMap::new()),
+            token_key,
+            validator,
+            signer,
+            allowlist: Arc::new(Allowlist::from_env()),
+            expected_domain: ExpectedDomain::from_env(),
+        }
+    }
+
+    /// Generate a fresh random nonce, record it as outstanding and return it.
+    pub(crate) fn issue_nonce(&self) -> String {
+        let mut raw = [0u8; 16];
+        OsRng.fill_bytes(&mut raw);
+        let nonce = hex::encode(raw);
+
+        let mut nonces = self.nonces.lock().expect("nonce store poisoned");
+        gc_expired(&mut nonces);
+        nonces.insert(
+            nonce.clone(),
+            NonceEntry {
+                issued_at: now(),
+                consumed: false,
+            },
+        );
+        nonce
+    }
+
+    /// Consume an outstanding nonce, returning an error if it is unknown,
+    /// already spent or past its TTL. This is what closes the replay window.
+    pub(crate) fn consume_nonce(&self, nonce: &str) -> Result<()> {
+        let mut nonces = self.nonces.lock().expect("nonce store poisoned");
+        let entry = nonces
+            .get_mut(nonce)
+            .ok_or_else(|| anyhow!("unknown or expired nonce"))?;
+        if entry.consumed {
+            return Err(anyhow!("nonce already used"));
+        }
+        if now().saturating_sub(entry.issued_at) > NONCE_TTL.as_secs() {
+            return Err(anyhow!("nonce expired"));
+        }
+        entry.consumed = true;
+        Ok(())
+    }
+
+    /// Mint an HMAC-authenticated session token of the form
+    /// `<address>.<expiry>.<hex-mac>` for the freshly authenticated signer.
+    pub(crate) fn issue_token(&self, address: Address) -> String {
+        let expiry = now() + SESSION_TTL.as_secs();
+        let payload = format!("{:?}.{}", address, expiry);
+        let mac = self.sign_token(&payload);
+        format!("{}.{}", payload, hex::encode(mac))
+    }
+
+    /// Validate a session token previously minted by [`AppState::issue_token`],
+    /// returning the bound address when the MAC checks out and the token is
+    /// unexpired.
+    fn verify_token(&self, token: &str) -> Result<Address> {
+        let (payload, mac_hex) = token
+            .rsplit_once('.')
+            .ok_or_else(|| anyhow!("malformed token"))?;
+        let provided = hex::decode(mac_hex).context("malformed token mac")?;
+        let mut mac =
+            HmacSha256::new_from_slice(&self.token_key).expect("HMAC accepts any key length");
+        mac.update(payload.as_bytes());
+        // `verify_slice` compares in constant time, unlike a plain `==` on the
+        // decoded bytes, so checking the MAC can't leak timing information.
+        mac.verify_slice(&provided)
+            .map_err(|_| anyhow!("bad token signature"))?;
+
+        let (addr_str, expiry_str) = payload
+            .split_once('.')
+            .ok_or_else(|| anyhow!("malformed token payload"))?;
+        let expiry: u64 = expiry_str.parse().context("malformed token expiry")?;
+        if now() > expiry {
+            return Err(anyhow!("token expired"));
+        }
+        Address::from_str(addr_str).context("malformed token address")
+    }
+
+    fn sign_token(&self, payload: &str) -> Vec<u8> {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.token_key).expect("HMAC accepts any key length");
+        mac.update(payload.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+/// Drop nonces whose TTL has elapsed so the store does not grow without bound.
+fn gc_expired(nonces: &mut HashMap<String, NonceEntry>) {
+    let cutoff = now();
+    nonces.retain(|_, entry| cutoff.saturating_sub(entry.issued_at) <= NONCE_TTL.as_secs());
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_secs()
+}
+
+/// A successfully authenticated caller. For the EIP-712 path the parsed domain
+/// is carried through so handlers can confirm the signature was scoped to them.
+struct Authenticated {
+    address: Address,
+    domain: Option<EIP712Domain>,
+}
+
+/// Resolve the caller's identity for a data request: either a valid session
+/// token (cookie or `Authorization: Bearer`) or a fresh signed request carried
+/// in the `X-Signature` header. The signature scheme defaults to `personal_sign`
+/// but switches to EIP-712 typed-data recovery when `X-Signature-Type: eip712`
+/// is present.
+///
+/// The `X-Signature` path is single-use: the caller must name a nonce issued by
+/// `GET /nonce` in `X-Nonce`, and that nonce is consumed before the signature is
+/// trusted. For `personal_sign`/validator signatures the nonce is folded into
+/// the signed bytes (`bind_nonce`) so the signature itself commits to it; for
+/// EIP-712 the typed-data `message` must carry a `nonce` field equal to it. A
+/// captured `(headers, body)` pair therefore cannot be replayed once its nonce
+/// has been spent.
+///
+/// For EIP-712, the recovered domain is also checked against the server's
+/// configured [`ExpectedDomain`] (when one is set) so a signature produced
+/// for a different dApp's domain separator is rejected rather than accepted
+/// just because its `primaryType` and nonce happen to line up.
+fn authenticate(state: &AppState, headers: &HeaderMap, body: &[u8]) -> Result<Authenticated> {
+    if let Some(token) = bearer_token(headers) {
+        return Ok(Authenticated {
+            address: state.verify_token(&token)?,
+            domain: None,
+        });
+    }
+
+    if let Some(signature_header) = headers.get("X-Signature") {
+        let signature = signature_header
+            .to_str()
+            .context("Failed to parse signature header")?;
+        let nonce = headers
+            .get("X-Nonce")
+            .ok_or_else(|| anyhow!("missing X-Nonce header"))?
+            .to_str()
+            .context("Failed to parse X-Nonce header")?;
+
+        let version = signature_version(headers)?;
+        if version == Eip191Version::TypedData {
+            let (address, domain) = recover_eip712(body, signature)?;
+            if let Some(expected) = &state.expected_domain {
+                expected.check(&domain)?;
+            }
+            let typed_nonce: TypedDataNonce =
+                serde_json::from_slice(body).context("Failed to parse typed data")?;
+            if typed_nonce.message.nonce != nonce {
+                return Err(anyhow!("typed data nonce does not match X-Nonce"));
+            }
+            state.consume_nonce(nonce)?;
+            return Ok(Authenticated {
+                address,
+                domain: Some(domain),
+            });
+        }
+
+        let bound = bind_nonce(nonce, body);
+        let address = recover_address_from_signature(&bound, signature, version, state.validator)?;
+        state.consume_nonce(nonce)?;
+        return Ok(Authenticated {
+            address,
+            domain: None,
+        });
+    }
+
+    Err(anyhow!("missing session token or X-Signature header"))
+}
+
+/// Fold a single-use `nonce` into the bytes that get signed, so a signature
+/// over `body` alone cannot be replayed once the nonce is spent.
+fn bind_nonce(nonce: &str, body: &[u8]) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(nonce.len() + 1 + body.len());
+    bound.extend_from_slice(nonce.as_bytes());
+    bound.push(b':');
+    bound.extend_from_slice(body);
+    bound
+}
+
+/// The EIP-191 version the request selects, via `X-Signature-Type`, defaulting
+/// to `personal_sign` (`0x45`).
+fn signature_version(headers: &HeaderMap) -> Result<Eip191Version> {
+    match headers.get("X-Signature-Type") {
+        Some(value) => Eip191Version::parse(
+            value
+                .to_str()
+                .context("Failed to parse X-Signature-Type header")?,
+        ),
+        None => Ok(Eip191Version::PersonalSign),
+    }
+}
+
+/// Extract a bearer session token from either the `Authorization` header or the
+/// `session` cookie, preferring the former.
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    if let Some(auth) = headers.get(header::AUTHORIZATION) {
+        if let Ok(value) = auth.to_str() {
+            if let Some(token) = value.strip_prefix("Bearer ") {
+                return Some(token.to_string());
+            }
+        }
+    }
+    let cookie = headers.get(header::COOKIE)?.to_str().ok()?;
+    cookie
+        .split(';')
+        .map(str::trim)
+        .find_map(|c| c.strip_prefix("session="))
+        .map(str::to_string)
+}
+
+pub(crate) fn text_response(status: StatusCode, msg: &str) -> Result<Response<Body>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(msg.to_string().into())
+        .context("Failed to build response")
+}
+
+async fn handle_request(state: Arc<AppState>, req: Request<Body>) -> Result<Response<Body>> {
+    log::debug!("Request: {:?}", req);
+
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/nonce") => return handle_nonce(&state),
+        (&Method::POST, "/verify") => return handle_verify(&state, req).await,
+        _ => {}
+    }
+
+    let (parts, body): (Parts, Body) = req.into_parts();
+    let body_bytes = hyper::body::to_bytes(body).await?;
+
+    // Non-Ethereum clients authenticate with an RFC-9421/draft-cavage
+    // `Signature:` header instead of `X-Signature`.
+    if parts.headers.contains_key("Signature") {
+        let key_id = match verify_http_signature(&parts, body_bytes.as_ref()) {
+            Ok(key_id) => key_id,
+            Err(e) => return text_response(StatusCode::UNAUTHORIZED, &format!("{e:?}")),
+        };
+
+        // Gate on the allowlist before doing any work for the caller, same as
+        // the Ethereum-signed path below.
+        let role = match state.allowlist.authorize_key_id(&key_id) {
+            Ok(role) => role,
+            Err(e) => return text_response(StatusCode::FORBIDDEN, &format!("{e:?}")),
+        };
+
+        let mut msg = format!("Signed by keyId: {key_id}");
+        if let Some(role) = role {
+            msg.push_str(&format!(" [role: {role}]"));
+        }
+        return sign_and_respond(&state, msg).await;
+    }
+
+    let Authenticated { address, domain } = match authenticate(&state, &parts.headers, body_bytes.as_ref())
+    {
+        Ok(authed) => authed,
+        Err(e) => return text_response(StatusCode::UNAUTHORIZED, &format!("{e:?}")),
+    };
+
+    // Gate on the allowlist before doing any work for the caller.
+    let role = match state.allowlist.authorize(&address) {
+        Ok(role) => role,
+        Err(e) => return text_response(StatusCode::FORBIDDEN, &format!("{e:?}")),
+    };
+
+    let mut msg = match domain {
+        Some(domain) => format!(
+            "Signed by address: {:?} (EIP-712 domain: {})",
+            address,
+            domain.name.as_deref().unwrap_or("<unnamed>")
+        ),
+        None => format!("Signed by address: {:?}", address),
+    };
+    if let Some(role) = role {
+        msg.push_str(&format!(" [role: {role}]"));
+    }
+
+    sign_and_respond(&state, msg).await
+}
+
+/// Build a plain-text response, counter-signing the body with the server's
+/// [`Signer`] (when configured) so clients can authenticate the server too.
+async fn sign_and_respond(state: &AppState, msg: String) -> Result<Response<Body>> {
+    let mut builder = Response::builder().header(header::CONTENT_TYPE, "text/plain");
+
+    if let Some(signer) = state.signer.as_ref() {
+        match signer.sign_message(msg.as_bytes()).await {
+            Ok(sig) => builder = builder.header("X-Server-Signature", sig.to_string()),
+            Err(e) => log::warn!("Failed to counter-sign response: {e:?}"),
+        }
+    }
+
+    builder
+        .body(msg.into())
+        .context("Failed to build response for an authenticated request")
+}
+
+const DEFAULT_IP: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 3000;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let ip = env::var("IP")
+        .unwrap_or_else(|_| DEFAULT_IP.to_string())
+        .parse::<Ipv4Addr>()
+        .context("Failed to parse IP")?;
+    let port = env::var("PORT")
+        .unwrap_or_else(|_| DEFAULT_PORT.to_string())
+        .parse::<u16>()
+        .context("Failed to parse PORT")?;
+
+    let addr = (ip, port).into();
+
+    let state = Arc::new(AppState::new().await);
+
+    // Keep the allowlist watcher alive for the lifetime of the server.
+    let _allowlist_watcher = watch_allowlist(state.allowlist.clone());
+
+    let make_svc = make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |req| {
+                let state = state.clone();
+                handle_request(state, req)
+            }))
+        }
+    });
+
+    let server = Server::bind(&addr).serve(make_svc);
+
+    log::info!("Listening on https://{}", addr);
+
+    server.await.context("Failed to start server")?;
+
+    Ok(())
+}
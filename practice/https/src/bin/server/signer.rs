@@ -0,0 +1,121 @@
+use std::{env, str::FromStr};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::core::types::{Address, Signature};
+
+/// The server's own signing identity. Abstracting over the backend keeps the
+/// USB-bound Ledger implementation behind a cargo feature so the default build
+/// stays USB-free, while callers only ever see [`sign_message`] and [`address`].
+///
+/// [`sign_message`]: Signer::sign_message
+/// [`address`]: Signer::address
+#[async_trait]
+pub(crate) trait Signer: Send + Sync {
+    /// Sign `msg` with the EIP-191 `personal_sign` scheme.
+    async fn sign_message(&self, msg: &[u8]) -> Result<Signature>;
+
+    /// The Ethereum address corresponding to this signer's key.
+    fn address(&self) -> Address;
+}
+
+/// A software signer built from a secp256k1 secret key held in memory.
+struct LocalWallet {
+    wallet: ethers::signers::LocalWallet,
+}
+
+impl LocalWallet {
+    /// Load the secret key from `SIGNER_PRIVATE_KEY` (a hex-encoded secp256k1
+    /// key, optionally `0x`-prefixed).
+    fn from_env() -> Result<Self> {
+        let key = env::var("SIGNER_PRIVATE_KEY")
+            .context("SIGNER_PRIVATE_KEY not set")?;
+        let wallet = ethers::signers::LocalWallet::from_str(key.trim_start_matches("0x"))
+            .context("Failed to parse SIGNER_PRIVATE_KEY")?;
+        Ok(Self { wallet })
+    }
+}
+
+#[async_trait]
+impl Signer for LocalWallet {
+    async fn sign_message(&self, msg: &[u8]) -> Result<Signature> {
+        use ethers::signers::Signer as _;
+        self.wallet
+            .sign_message(msg)
+            .await
+            .context("Failed to sign message with local wallet")
+    }
+
+    fn address(&self) -> Address {
+        use ethers::signers::Signer as _;
+        self.wallet.address()
+    }
+}
+
+/// A hardware signer backed by the Ledger Nano Ethereum app over USB HID. Kept
+/// behind the `ledger` feature so the default build pulls in no USB stack.
+#[cfg(feature = "ledger")]
+struct LedgerSigner {
+    ledger: ethers::signers::Ledger,
+}
+
+#[cfg(feature = "ledger")]
+impl LedgerSigner {
+    /// Connect to an attached Ledger. The derivation path is taken from
+    /// `LEDGER_HD_PATH` (defaulting to the standard `m/44'/60'/0'/0/0`) and the
+    /// EIP-155 chain id from `CHAIN_ID` (defaulting to mainnet).
+    async fn connect() -> Result<Self> {
+        use ethers::signers::{HDPath, Ledger};
+
+        let path = env::var("LEDGER_HD_PATH")
+            .map(HDPath::Other)
+            .unwrap_or(HDPath::LedgerLive(0));
+        let chain_id: u64 = env::var("CHAIN_ID")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1);
+        let ledger = Ledger::new(path, chain_id)
+            .await
+            .context("Failed to connect to Ledger device")?;
+        Ok(Self { ledger })
+    }
+}
+
+#[cfg(feature = "ledger")]
+#[async_trait]
+impl Signer for LedgerSigner {
+    async fn sign_message(&self, msg: &[u8]) -> Result<Signature> {
+        use ethers::signers::Signer as _;
+        self.ledger
+            .sign_message(msg)
+            .await
+            .context("Failed to sign message with Ledger")
+    }
+
+    fn address(&self) -> Address {
+        use ethers::signers::Signer as _;
+        self.ledger.address()
+    }
+}
+
+/// Build the server's signer from the environment, if one is configured. The
+/// Ledger backend is preferred when compiled in, `USE_LEDGER` is set and a
+/// device is reachable; otherwise a `LocalWallet` is used when
+/// `SIGNER_PRIVATE_KEY` is present.
+pub(crate) async fn load_signer() -> Option<Box<dyn Signer>> {
+    #[cfg(feature = "ledger")]
+    if env::var("USE_LEDGER").is_ok() {
+        match LedgerSigner::connect().await {
+            Ok(signer) => return Some(Box::new(signer)),
+            Err(e) => log::warn!("Ledger signer unavailable: {e:?}"),
+        }
+    }
+
+    match LocalWallet::from_env() {
+        Ok(signer) => Some(Box::new(signer)),
+        Err(e) => {
+            log::info!("No local signer configured: {e:?}");
+            None
+        }
+    }
+}